@@ -0,0 +1,320 @@
+use crate::model::{NowPlaying, PlayerState};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::env;
+use std::io::{self, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::thread::sleep;
+use std::time::Duration;
+
+use crate::unix_now_secs;
+
+const CLIENT_ID: &str = "1470151628547031280";
+const FALLBACK_ARTWORK: &str = "am_icon_001";
+const RECONNECT_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const RECONNECT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+const ARTWORK_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize)]
+struct Handshake<'a> {
+    v: u8,
+    client_id: &'a str,
+}
+
+#[derive(Serialize)]
+struct SetActivityCommand<'a> {
+    cmd: &'a str,
+    nonce: String,
+    args: ActivityArgs<'a>,
+}
+
+#[derive(Serialize)]
+struct ActivityArgs<'a> {
+    pid: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    activity: Option<Activity<'a>>,
+}
+
+#[derive(Serialize)]
+struct Activity<'a> {
+    name: &'a str,
+    r#type: u8,
+    details: &'a str,
+    state: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamps: Option<Timestamps>,
+    assets: Assets,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    buttons: Option<Vec<Button>>,
+}
+
+#[derive(Serialize)]
+struct Button {
+    label: &'static str,
+    url: String,
+}
+
+#[derive(Serialize)]
+struct Timestamps {
+    start: i64,
+    end: i64,
+}
+
+#[derive(Serialize)]
+struct Assets {
+    large_image: String,
+}
+
+/// Owns the Discord IPC socket and the Apple Music presence that's currently
+/// published to it. This is the reusable sink half of the crate: feed it
+/// `NowPlaying` values from any `MusicSource` and it takes care of the wire
+/// protocol and artwork lookups.
+pub struct DiscordPresence {
+    stream: UnixStream,
+    artwork_cache: HashMap<(String, String), String>,
+}
+
+impl DiscordPresence {
+    pub fn connect() -> io::Result<Self> {
+        let mut stream = try_connect_discord_ipc()?;
+        send_handshake(&mut stream, CLIENT_ID)?;
+        let (_op, _resp) = read_frame(&mut stream)?;
+
+        Ok(Self {
+            stream,
+            artwork_cache: HashMap::new(),
+        })
+    }
+
+    pub fn set_now_playing(&mut self, np: &NowPlaying) -> io::Result<()> {
+        let timestamps = if np.state == PlayerState::Playing {
+            let start = unix_now_secs() - np.position_secs.floor() as i64;
+            let end = start + np.duration_secs.floor() as i64;
+            Some(Timestamps { start, end })
+        } else {
+            None
+        };
+
+        let large_image = fetch_artwork_url(&np.artist, &np.track, &mut self.artwork_cache);
+        let track_url = track_url(np);
+
+        let command = SetActivityCommand {
+            cmd: "SET_ACTIVITY",
+            nonce: unix_now_secs().to_string(),
+            args: ActivityArgs {
+                pid: std::process::id(),
+                activity: Some(Activity {
+                    name: "Apple Music",
+                    r#type: 2,
+                    details: &np.track,
+                    state: np.state_string(),
+                    timestamps,
+                    assets: Assets { large_image },
+                    buttons: Some(vec![Button {
+                        label: "Listen on Apple Music",
+                        url: track_url,
+                    }]),
+                }),
+            },
+        };
+
+        let payload = serde_json::to_string(&command).unwrap();
+        self.send_command(&payload);
+        Ok(())
+    }
+
+    pub fn clear(&mut self) -> io::Result<()> {
+        let command = SetActivityCommand {
+            cmd: "SET_ACTIVITY",
+            nonce: unix_now_secs().to_string(),
+            args: ActivityArgs {
+                pid: std::process::id(),
+                activity: None,
+            },
+        };
+
+        let payload = serde_json::to_string(&command).unwrap();
+        self.send_command(&payload);
+        Ok(())
+    }
+
+    /// Sends a SET_ACTIVITY payload, reconnecting with backoff and retrying
+    /// on any IPC error so a Discord restart never takes the daemon down
+    /// with it.
+    fn send_command(&mut self, payload: &str) {
+        loop {
+            let result = send_frame(&mut self.stream, 1, payload).and_then(|()| read_frame(&mut self.stream));
+
+            match result {
+                Ok(_) => return,
+                Err(err) => {
+                    eprintln!("⚠️  Discord IPC error ({}), reconnecting...", err);
+                    self.reconnect();
+                }
+            }
+        }
+    }
+
+    /// Blocks until a fresh handshake succeeds, backing off exponentially
+    /// between attempts (capped at `RECONNECT_MAX_BACKOFF`).
+    fn reconnect(&mut self) {
+        let mut backoff = RECONNECT_INITIAL_BACKOFF;
+
+        loop {
+            let attempt = try_connect_discord_ipc().and_then(|mut stream| {
+                send_handshake(&mut stream, CLIENT_ID)?;
+                read_frame(&mut stream)?;
+                Ok(stream)
+            });
+
+            match attempt {
+                Ok(stream) => {
+                    self.stream = stream;
+                    println!("✅ Reconnected to Discord IPC");
+                    return;
+                }
+                Err(err) => {
+                    eprintln!(
+                        "⚠️  Reconnect failed ({}), retrying in {:?}",
+                        err, backoff
+                    );
+                    sleep(backoff);
+                    backoff = (backoff * 2).min(RECONNECT_MAX_BACKOFF);
+                }
+            }
+        }
+    }
+}
+
+fn candidate_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    if let Ok(p) = env::var("DISCORD_IPC_PATH") {
+        dirs.push(PathBuf::from(p));
+    }
+    if let Ok(p) = env::var("TMPDIR") {
+        dirs.push(PathBuf::from(p));
+    }
+    dirs.push(PathBuf::from("/tmp"));
+
+    dirs
+}
+
+fn try_connect_discord_ipc() -> io::Result<UnixStream> {
+    let dirs = candidate_dirs();
+    for dir in dirs {
+        for i in 0..10 {
+            let path = dir.join(format!("discord-ipc-{}", i));
+
+            if path.exists() {
+                if let Ok(stream) = UnixStream::connect(&path) {
+                    println!("✅ Connected to Discord IPC at: {}", path.display());
+                    return Ok(stream);
+                }
+            }
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "Could not find Discord IPC socket",
+    ))
+}
+
+fn send_frame(stream: &mut UnixStream, op: u32, payload_json: &str) -> io::Result<()> {
+    let payload = payload_json.as_bytes();
+    let len = payload.len() as u32;
+
+    stream.write_all(&op.to_le_bytes())?;
+    stream.write_all(&len.to_le_bytes())?;
+    stream.write_all(payload)?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn read_frame(stream: &mut UnixStream) -> io::Result<(u32, String)> {
+    let mut header = [0u8; 8];
+    stream.read_exact(&mut header)?;
+
+    let op = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+    let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+
+    let mut payload = vec![0u8; len];
+    stream.read_exact(&mut payload)?;
+
+    let json = String::from_utf8_lossy(&payload).to_string();
+    Ok((op, json))
+}
+
+fn send_handshake(stream: &mut UnixStream, client_id: &str) -> io::Result<()> {
+    let handshake = Handshake { v: 1, client_id };
+    let payload = serde_json::to_string(&handshake).unwrap();
+    send_frame(stream, 0, &payload)
+}
+
+/// Looks up cover art for a track via the iTunes Search API, upscaling the
+/// thumbnail to 512x512. Results are cached by (artist, track) since the
+/// main loop polls every few seconds and the artwork never changes between
+/// polls of the same song. Only a successful lookup is cached — a transient
+/// request failure leaves the key uncached so the next poll gets to retry
+/// instead of pinning the fallback icon for the rest of the track.
+fn fetch_artwork_url(artist: &str, track: &str, cache: &mut HashMap<(String, String), String>) -> String {
+    let key = (artist.to_string(), track.to_string());
+    if let Some(url) = cache.get(&key) {
+        return url.clone();
+    }
+
+    match lookup_artwork(artist, track) {
+        Ok(Some(url)) => {
+            cache.insert(key, url.clone());
+            url
+        }
+        Ok(None) => {
+            cache.insert(key, FALLBACK_ARTWORK.to_string());
+            FALLBACK_ARTWORK.to_string()
+        }
+        Err(err) => {
+            eprintln!("⚠️  iTunes artwork lookup failed ({}), will retry next poll", err);
+            FALLBACK_ARTWORK.to_string()
+        }
+    }
+}
+
+/// `Ok(Some(url))` is a genuine match, `Ok(None)` is a genuine no-match, and
+/// `Err` covers request/timeout failures — callers need to tell these apart
+/// to decide what's safe to cache.
+fn lookup_artwork(artist: &str, track: &str) -> Result<Option<String>, reqwest::Error> {
+    let term = format!("{} {}", artist, track);
+    let client = reqwest::blocking::Client::builder()
+        .timeout(ARTWORK_REQUEST_TIMEOUT)
+        .build()?;
+
+    let response = client
+        .get(format!(
+            "https://itunes.apple.com/search?term={}&entity=song&limit=1",
+            urlencoding::encode(&term)
+        ))
+        .send()?
+        .json::<serde_json::Value>()?;
+
+    let artwork_url_100 = response
+        .get("results")
+        .and_then(|results| results.get(0))
+        .and_then(|result| result.get("artworkUrl100"))
+        .and_then(|url| url.as_str());
+
+    Ok(artwork_url_100.map(|url| url.replace("100x100", "512x512")))
+}
+
+/// Resolves the store URL for the "Listen on Apple Music" button, falling
+/// back to a search link when AppleScript couldn't give us the track's own
+/// URL (the usual case for purchased/library tracks).
+fn track_url(np: &NowPlaying) -> String {
+    np.url.clone().unwrap_or_else(|| {
+        let term = format!("{} {}", np.artist, np.track);
+        format!(
+            "https://music.apple.com/search?term={}",
+            urlencoding::encode(&term)
+        )
+    })
+}
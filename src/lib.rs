@@ -0,0 +1,21 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub mod ipc;
+pub mod model;
+pub mod scheduler;
+#[cfg(feature = "http-server")]
+pub mod server;
+pub mod source;
+#[cfg(feature = "stats")]
+pub mod stats;
+
+pub use ipc::DiscordPresence;
+pub use model::{NowPlaying, PlayerState};
+pub use source::{AppleMusicSource, MusicSource};
+
+pub(crate) fn unix_now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
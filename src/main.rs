@@ -1,284 +1,83 @@
-use serde::Serialize;
-use std::env;
-use std::io::{self, Read, Write};
-use std::os::unix::net::UnixStream;
-use std::path::PathBuf;
-use std::process::Command;
+use apple_music_rpc::scheduler::{detect_seek, next_poll_interval};
+#[cfg(feature = "http-server")]
+use apple_music_rpc::server;
+#[cfg(feature = "stats")]
+use apple_music_rpc::stats::ListeningStats;
+use apple_music_rpc::{AppleMusicSource, DiscordPresence, MusicSource, PlayerState};
+use std::io;
+#[cfg(feature = "http-server")]
+use std::sync::{Arc, Mutex};
 use std::thread::sleep;
-use std::time::Duration;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::Instant;
 
-const CLIENT_ID: &str = "1470151628547031280";
+#[cfg(feature = "http-server")]
+const CONTROL_SERVER_ADDR: &str = "127.0.0.1:7676";
 
-#[derive(Serialize)]
-struct Handshake<'a> {
-    v: u8,
-    client_id: &'a str,
+#[cfg(feature = "stats")]
+fn stats_db_path() -> std::path::PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    std::path::PathBuf::from(home)
+        .join(".apple-music-rpc")
+        .join("stats.sqlite3")
 }
 
-#[derive(Serialize)]
-struct SetActivityCommand<'a> {
-    cmd: &'a str,
-    nonce: String,
-    args: ActivityArgs<'a>,
-}
-
-#[derive(Serialize)]
-struct ActivityArgs<'a> {
-    pid: u32,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    activity: Option<Activity<'a>>,
-}
-
-#[derive(Serialize)]
-struct Activity<'a> {
-    name: &'a str,
-    r#type: u8,
-    details: &'a str,
-    state: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    timestamps: Option<Timestamps>,
-    assets: Assets<'a>,
-}
-
-#[derive(Serialize)]
-struct Timestamps {
-    start: i64,
-    end: i64,
-}
-
-#[derive(Serialize)]
-struct Assets<'a> {
-    large_image: &'a str,
-}
-
-#[derive(Debug, PartialEq, Eq, Hash)]
-enum PlayerState {
-    Playing,
-    Paused,
-    Stopped,
-}
-
-#[derive(Debug)]
-struct NowPlaying {
-    track: String,
-    artist: String,
-    album: String,
-    state: PlayerState,
-    position_secs: f32,
-    duration_secs: f32,
-}
-
-impl NowPlaying {
-    fn key(&self) -> (&str, &str, &str) {
-        (&self.track, &self.artist, &self.album)
-    }
-
-    fn state_string(&self) -> String {
-        if self.album.is_empty() {
-            self.artist.clone()
-        } else {
-            format!("{} • {}", self.artist, self.album)
-        }
+#[cfg(feature = "stats")]
+fn open_stats() -> Option<ListeningStats> {
+    let path = stats_db_path();
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
     }
-}
-
-fn candidate_dirs() -> Vec<PathBuf> {
-    let mut dirs = Vec::new();
-
-    if let Ok(p) = env::var("DISCORD_IPC_PATH") {
-        dirs.push(PathBuf::from(p));
-    }
-    if let Ok(p) = env::var("TMPDIR") {
-        dirs.push(PathBuf::from(p));
-    }
-    dirs.push(PathBuf::from("/tmp"));
 
-    dirs
-}
-
-fn try_connect_discord_ipc() -> io::Result<UnixStream> {
-    let dirs = candidate_dirs();
-    for dir in dirs {
-        for i in 0..10 {
-            let path = dir.join(format!("discord-ipc-{}", i));
-
-            if path.exists() {
-                if let Ok(stream) = UnixStream::connect(&path) {
-                    println!("✅ Connected to Discord IPC at: {}", path.display());
-                    return Ok(stream);
-                }
-            }
+    match ListeningStats::open(&path) {
+        Ok(stats) => Some(stats),
+        Err(err) => {
+            eprintln!(
+                "⚠️  failed to open stats database at {} ({}), stats disabled",
+                path.display(),
+                err
+            );
+            None
         }
     }
-    Err(io::Error::new(
-        io::ErrorKind::NotFound,
-        "Could not find Discord IPC socket",
-    ))
-}
-
-fn send_frame(stream: &mut UnixStream, op: u32, payload_json: &str) -> io::Result<()> {
-    let payload = payload_json.as_bytes();
-    let len = payload.len() as u32;
-
-    stream.write_all(&op.to_le_bytes())?;
-    stream.write_all(&len.to_le_bytes())?;
-    stream.write_all(payload)?;
-    stream.flush()?;
-    Ok(())
 }
 
-fn read_frame(stream: &mut UnixStream) -> io::Result<(u32, String)> {
-    let mut header = [0u8; 8];
-    stream.read_exact(&mut header)?;
-
-    let op = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
-    let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
-
-    let mut payload = vec![0u8; len];
-    stream.read_exact(&mut payload)?;
-
-    let json = String::from_utf8_lossy(&payload).to_string();
-    Ok((op, json))
-}
-
-fn send_handshake(stream: &mut UnixStream, client_id: &str) -> io::Result<()> {
-    let handshake = Handshake { v: 1, client_id };
-    let payload = serde_json::to_string(&handshake).unwrap();
-    send_frame(stream, 0, &payload)
-}
-
-fn set_activity_now_playing(stream: &mut UnixStream, np: &NowPlaying) -> io::Result<()> {
-    let timestamps = if np.state == PlayerState::Playing {
-        let start = unix_now_secs() - np.position_secs.floor() as i64;
-        let end = start + np.duration_secs.floor() as i64;
-        Some(Timestamps { start, end })
-    } else {
-        None
-    };
-
-    let command = SetActivityCommand {
-        cmd: "SET_ACTIVITY",
-        nonce: unix_now_secs().to_string(),
-        args: ActivityArgs {
-            pid: std::process::id(),
-            activity: Some(Activity {
-                name: "Apple Music",
-                r#type: 2,
-                details: &np.track,
-                state: np.state_string(),
-                timestamps,
-                assets: Assets {
-                    large_image: "am_icon_001",
-                },
-            }),
-        },
-    };
-
-    let payload = serde_json::to_string(&command).unwrap();
-    send_frame(stream, 1, &payload)
-}
-
-fn clear_activity(stream: &mut UnixStream) -> io::Result<()> {
-    let command = SetActivityCommand {
-        cmd: "SET_ACTIVITY",
-        nonce: unix_now_secs().to_string(),
-        args: ActivityArgs {
-            pid: std::process::id(),
-            activity: None,
-        },
-    };
-
-    let payload = serde_json::to_string(&command).unwrap();
-    send_frame(stream, 1, &payload)
-}
-
-fn read_apple_music_raw() -> Option<String> {
-    let script = r#"
-    tell application "Music"
-        if not (it is running) then
-            return "STOPPED"
-        end if
-
-        set ps to player state as text
-        if ps is not "playing" and ps is not "paused" then
-            return "STOPPED"
-        end if
-
-        set t to current track
-        return (name of t) & "||" & (artist of t) & "||" & (album of t) & "||" & ps & "||" & (player position) & "||" & (duration of t)
-    end tell
-    "#;
-
-    let output = Command::new("osascript")
-        .arg("-e")
-        .arg(script)
-        .output()
-        .ok()?;
-
-    let text = String::from_utf8_lossy(&output.stdout);
-    let trimmed = text.trim();
-
-    if trimmed.is_empty() || trimmed == "STOPPED" {
-        None
-    } else {
-        Some(trimmed.to_string())
-    }
-}
-
-fn parse_state(state_str: &str) -> PlayerState {
-    match state_str {
-        "playing" => PlayerState::Playing,
-        "paused" => PlayerState::Paused,
-        _ => PlayerState::Stopped,
-    }
-}
-
-fn parse_f32(s: &str) -> Option<f32> {
-    s.replace(',', ".").parse().ok()
-}
-
-fn parse_now_playing(raw: &str) -> Option<NowPlaying> {
-    let mut parts = raw.split("||").map(str::trim);
-
-    Some(NowPlaying {
-        track: parts.next()?.to_string(),
-        artist: parts.next()?.to_string(),
-        album: parts.next()?.to_string(),
-        state: parse_state(parts.next()?),
-        position_secs: parse_f32(parts.next()?)?,
-        duration_secs: parse_f32(parts.next()?)?,
-    })
-}
+fn main() -> io::Result<()> {
+    let mut presence = DiscordPresence::connect()?;
+    let source = AppleMusicSource;
 
-fn unix_now_secs() -> i64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64
-}
+    #[cfg(feature = "http-server")]
+    let shared_now_playing: server::SharedNowPlaying = Arc::new(Mutex::new(None));
+    #[cfg(feature = "http-server")]
+    server::spawn(CONTROL_SERVER_ADDR, shared_now_playing.clone());
 
-fn main() -> io::Result<()> {
-    let mut stream = try_connect_discord_ipc()?;
-    send_handshake(&mut stream, CLIENT_ID)?;
-    let (_op, _resp) = read_frame(&mut stream)?;
+    #[cfg(feature = "stats")]
+    let mut stats = open_stats();
 
     let mut last_key: Option<(String, String, String)> = None;
     let mut last_state: Option<PlayerState> = None;
+    let mut last_poll: Option<(f32, Instant)> = None;
     let mut was_stopped = true;
 
     loop {
-        let current = read_apple_music_raw().and_then(|raw| parse_now_playing(&raw));
+        let polled_at = Instant::now();
+        let current = source.poll();
+
+        #[cfg(feature = "http-server")]
+        {
+            *shared_now_playing.lock().unwrap() = current.clone();
+        }
+
+        let sleep_duration = next_poll_interval(current.as_ref());
 
         match current {
             None => {
                 if !was_stopped {
-                    clear_activity(&mut stream)?;
-                    let _ = read_frame(&mut stream);
+                    presence.clear()?;
                     was_stopped = true;
                     last_key = None;
                     last_state = None;
                 }
+                last_poll = None;
             }
             Some(np) => {
                 was_stopped = false;
@@ -289,16 +88,39 @@ fn main() -> io::Result<()> {
                     .as_ref()
                     .map(|(t, ar, al)| (t.as_str(), ar.as_str(), al.as_str()))
                     != Some(key);
-
-                if track_changed || state_changed {
-                    set_activity_now_playing(&mut stream, &np)?;
-                    let _ = read_frame(&mut stream);
-                    last_key = Some((np.track, np.artist, np.album));
-                    last_state = Some(np.state);
+                let seeked = !track_changed
+                    && !state_changed
+                    && last_state == Some(PlayerState::Playing)
+                    && last_poll
+                        .map(|(pos, at)| detect_seek(pos, polled_at.duration_since(at), np.position_secs))
+                        .unwrap_or(false);
+
+                if track_changed || state_changed || seeked {
+                    presence.set_now_playing(&np)?;
+
+                    #[cfg(feature = "stats")]
+                    if track_changed {
+                        if let Some(stats) = stats.as_mut() {
+                            stats.on_track_change(&np);
+                            println!(
+                                "📊 {} tracks played this session (most played: {})",
+                                stats.session_play_count(),
+                                stats
+                                    .most_played_artist()
+                                    .map(|(artist, count)| format!("{} x{}", artist, count))
+                                    .unwrap_or_else(|| "n/a".to_string())
+                            );
+                        }
+                    }
+
+                    last_key = Some((np.track.clone(), np.artist.clone(), np.album.clone()));
+                    last_state = Some(np.state.clone());
                 }
+
+                last_poll = Some((np.position_secs, polled_at));
             }
         }
 
-        sleep(Duration::from_secs(3));
+        sleep(sleep_duration);
     }
 }
@@ -0,0 +1,33 @@
+use serde::Serialize;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize)]
+pub enum PlayerState {
+    Playing,
+    Paused,
+    Stopped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NowPlaying {
+    pub track: String,
+    pub artist: String,
+    pub album: String,
+    pub state: PlayerState,
+    pub position_secs: f32,
+    pub duration_secs: f32,
+    pub url: Option<String>,
+}
+
+impl NowPlaying {
+    pub fn key(&self) -> (&str, &str, &str) {
+        (&self.track, &self.artist, &self.album)
+    }
+
+    pub fn state_string(&self) -> String {
+        if self.album.is_empty() {
+            self.artist.clone()
+        } else {
+            format!("{} • {}", self.artist, self.album)
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::model::{NowPlaying, PlayerState};
+use std::time::Duration;
+
+/// Ceiling on how long we'll sleep while a track is playing — we still want
+/// to notice state changes (pause, skip) reasonably quickly even mid-track.
+pub const POLL_INTERVAL_PLAYING_MAX: Duration = Duration::from_secs(3);
+pub const POLL_INTERVAL_PAUSED: Duration = Duration::from_secs(10);
+pub const POLL_INTERVAL_STOPPED: Duration = Duration::from_secs(15);
+
+/// How far the observed position has to drift from the expected one before
+/// we call it a seek rather than poll jitter.
+pub const SEEK_THRESHOLD_SECS: f32 = 2.0;
+
+/// Picks how long to sleep before the next poll. While playing, we sleep
+/// just past the moment the track is expected to end (so the switch to the
+/// next track is caught promptly), capped at `POLL_INTERVAL_PLAYING_MAX` so
+/// long songs don't starve us of state-change updates.
+pub fn next_poll_interval(now_playing: Option<&NowPlaying>) -> Duration {
+    match now_playing {
+        Some(np) if np.state == PlayerState::Playing => {
+            let remaining = (np.duration_secs - np.position_secs).max(0.0);
+            let wait = (remaining + 0.5).min(POLL_INTERVAL_PLAYING_MAX.as_secs_f32());
+            Duration::from_secs_f32(wait)
+        }
+        Some(_) => POLL_INTERVAL_PAUSED,
+        None => POLL_INTERVAL_STOPPED,
+    }
+}
+
+/// Detects a seek by comparing where playback "should" be (last known
+/// position plus wall-clock time elapsed) against where it actually is.
+pub fn detect_seek(last_position_secs: f32, elapsed: Duration, observed_position_secs: f32) -> bool {
+    let expected = last_position_secs + elapsed.as_secs_f32();
+    (expected - observed_position_secs).abs() > SEEK_THRESHOLD_SECS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now_playing(state: PlayerState, position_secs: f32, duration_secs: f32) -> NowPlaying {
+        NowPlaying {
+            track: "Track".to_string(),
+            artist: "Artist".to_string(),
+            album: "Album".to_string(),
+            state,
+            position_secs,
+            duration_secs,
+            url: None,
+        }
+    }
+
+    #[test]
+    fn playing_interval_tracks_remaining_time() {
+        let np = now_playing(PlayerState::Playing, 10.0, 12.0);
+        assert_eq!(next_poll_interval(Some(&np)), Duration::from_secs_f32(2.5));
+    }
+
+    #[test]
+    fn playing_interval_caps_at_max_for_long_remaining() {
+        let np = now_playing(PlayerState::Playing, 0.0, 300.0);
+        assert_eq!(next_poll_interval(Some(&np)), POLL_INTERVAL_PLAYING_MAX);
+    }
+
+    #[test]
+    fn paused_interval_backs_off() {
+        let np = now_playing(PlayerState::Paused, 10.0, 200.0);
+        assert_eq!(next_poll_interval(Some(&np)), POLL_INTERVAL_PAUSED);
+    }
+
+    #[test]
+    fn stopped_interval_is_slow() {
+        assert_eq!(next_poll_interval(None), POLL_INTERVAL_STOPPED);
+    }
+
+    #[test]
+    fn seek_not_detected_within_threshold() {
+        assert!(!detect_seek(10.0, Duration::from_secs(1), 12.9));
+    }
+
+    #[test]
+    fn seek_not_detected_exactly_at_threshold() {
+        assert!(!detect_seek(10.0, Duration::from_secs(1), 13.0));
+    }
+
+    #[test]
+    fn seek_detected_past_threshold() {
+        assert!(detect_seek(10.0, Duration::from_secs(1), 13.1));
+    }
+}
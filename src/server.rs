@@ -0,0 +1,89 @@
+use crate::model::NowPlaying;
+use serde::Serialize;
+use std::io;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Shared handle the main poll loop writes into and the control server reads
+/// from, so an HTTP client always sees the most recently polled track.
+pub type SharedNowPlaying = Arc<Mutex<Option<NowPlaying>>>;
+
+#[derive(Serialize)]
+#[serde(tag = "type", content = "content")]
+enum ApiResponse<T> {
+    Success(T),
+    Failure(String),
+}
+
+/// Starts the control/status server on its own thread. Runs alongside the
+/// main poll loop for as long as the process is alive.
+pub fn spawn(addr: &str, now_playing: SharedNowPlaying) {
+    let addr = addr.to_string();
+    thread::spawn(move || {
+        let server = match tiny_http::Server::http(&addr) {
+            Ok(server) => server,
+            Err(err) => {
+                eprintln!("⚠️  failed to start control server on {}: {}", addr, err);
+                return;
+            }
+        };
+
+        println!("🌐 Control server listening on http://{}", addr);
+
+        for request in server.incoming_requests() {
+            handle_request(request, &now_playing);
+        }
+    });
+}
+
+fn handle_request(request: tiny_http::Request, now_playing: &SharedNowPlaying) {
+    let (status, body) = match (request.method(), request.url()) {
+        (tiny_http::Method::Get, "/api/v1/now-playing") => now_playing_response(now_playing),
+        (tiny_http::Method::Post, "/api/v1/play") => control_response(run_music_command("play")),
+        (tiny_http::Method::Post, "/api/v1/pause") => control_response(run_music_command("pause")),
+        (tiny_http::Method::Post, "/api/v1/next") => control_response(run_music_command("next track")),
+        _ => (404, json(&ApiResponse::<()>::Failure("not found".to_string()))),
+    };
+
+    let header =
+        tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap();
+    let response = tiny_http::Response::from_string(body)
+        .with_status_code(status)
+        .with_header(header);
+    let _ = request.respond(response);
+}
+
+fn now_playing_response(now_playing: &SharedNowPlaying) -> (u16, String) {
+    match now_playing.lock().unwrap().as_ref() {
+        Some(np) => (200, json(&ApiResponse::Success(np))),
+        None => (
+            200,
+            json(&ApiResponse::<()>::Failure("Music is not playing".to_string())),
+        ),
+    }
+}
+
+fn control_response(result: io::Result<()>) -> (u16, String) {
+    match result {
+        Ok(()) => (200, json(&ApiResponse::Success(()))),
+        Err(err) => (502, json(&ApiResponse::<()>::Failure(err.to_string()))),
+    }
+}
+
+/// Drives Apple Music via `osascript`, the same mechanism the polling side
+/// uses to read its state.
+fn run_music_command(command: &str) -> io::Result<()> {
+    let script = format!("tell application \"Music\" to {}", command);
+    let status = Command::new("osascript").arg("-e").arg(script).status()?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other("osascript exited with a non-zero status"))
+    }
+}
+
+fn json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap()
+}
@@ -0,0 +1,130 @@
+use crate::model::{NowPlaying, PlayerState};
+use std::process::Command;
+
+/// A pluggable source of "what's playing right now". `AppleMusicSource` is
+/// the only implementation today, but this keeps the polling loop and the
+/// Discord presence layer from caring how a `NowPlaying` is produced.
+pub trait MusicSource {
+    fn poll(&self) -> Option<NowPlaying>;
+}
+
+pub struct AppleMusicSource;
+
+impl MusicSource for AppleMusicSource {
+    fn poll(&self) -> Option<NowPlaying> {
+        read_apple_music_raw().and_then(|raw| parse_now_playing(&raw))
+    }
+}
+
+fn read_apple_music_raw() -> Option<String> {
+    let script = r#"
+    tell application "Music"
+        if not (it is running) then
+            return "STOPPED"
+        end if
+
+        set ps to player state as text
+        if ps is not "playing" and ps is not "paused" then
+            return "STOPPED"
+        end if
+
+        set t to current track
+        set trackUrl to ""
+        try
+            set trackUrl to (address of t)
+        end try
+
+        return (name of t) & "||" & (artist of t) & "||" & (album of t) & "||" & ps & "||" & (player position) & "||" & (duration of t) & "||" & trackUrl
+    end tell
+    "#;
+
+    let output = Command::new("osascript")
+        .arg("-e")
+        .arg(script)
+        .output()
+        .ok()?;
+
+    let text = String::from_utf8_lossy(&output.stdout);
+    let trimmed = text.trim();
+
+    if trimmed.is_empty() || trimmed == "STOPPED" {
+        None
+    } else {
+        Some(trimmed.to_string())
+    }
+}
+
+fn parse_state(state_str: &str) -> PlayerState {
+    match state_str {
+        "playing" => PlayerState::Playing,
+        "paused" => PlayerState::Paused,
+        _ => PlayerState::Stopped,
+    }
+}
+
+fn parse_f32(s: &str) -> Option<f32> {
+    s.replace(',', ".").parse().ok()
+}
+
+fn parse_now_playing(raw: &str) -> Option<NowPlaying> {
+    let mut parts = raw.split("||").map(str::trim);
+
+    Some(NowPlaying {
+        track: parts.next()?.to_string(),
+        artist: parts.next()?.to_string(),
+        album: parts.next()?.to_string(),
+        state: parse_state(parts.next()?),
+        position_secs: parse_f32(parts.next()?)?,
+        duration_secs: parse_f32(parts.next()?)?,
+        url: parts.next().filter(|s| !s.is_empty()).map(str::to_string),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_playing_track_with_url() {
+        let np = parse_now_playing(
+            "Track Name||Some Artist||Some Album||playing||12.5||200||https://music.apple.com/song/1",
+        )
+        .unwrap();
+
+        assert_eq!(np.track, "Track Name");
+        assert_eq!(np.artist, "Some Artist");
+        assert_eq!(np.album, "Some Album");
+        assert_eq!(np.state, PlayerState::Playing);
+        assert_eq!(np.position_secs, 12.5);
+        assert_eq!(np.duration_secs, 200.0);
+        assert_eq!(np.url.as_deref(), Some("https://music.apple.com/song/1"));
+    }
+
+    #[test]
+    fn parses_missing_url_as_none() {
+        let np = parse_now_playing("Track||Artist||Album||paused||0||180||").unwrap();
+
+        assert_eq!(np.state, PlayerState::Paused);
+        assert_eq!(np.url, None);
+    }
+
+    #[test]
+    fn parses_comma_decimal_separator() {
+        let np = parse_now_playing("Track||Artist||Album||playing||12,5||200,0||").unwrap();
+
+        assert_eq!(np.position_secs, 12.5);
+        assert_eq!(np.duration_secs, 200.0);
+    }
+
+    #[test]
+    fn unknown_state_falls_back_to_stopped() {
+        let np = parse_now_playing("Track||Artist||Album||weird||0||0||").unwrap();
+
+        assert_eq!(np.state, PlayerState::Stopped);
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        assert!(parse_now_playing("Track||Artist").is_none());
+    }
+}
@@ -0,0 +1,97 @@
+use crate::model::NowPlaying;
+use crate::unix_now_secs;
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Instant;
+
+struct PendingPlay {
+    track: String,
+    artist: String,
+    album: String,
+    started_unix: i64,
+    started_at: Instant,
+}
+
+/// Lightweight scrobble-style play history. A record is written every time
+/// the main loop sees a genuine track change, so users who run the daemon
+/// continuously end up with a local play log for free.
+pub struct ListeningStats {
+    conn: Connection,
+    pending: Option<PendingPlay>,
+    session_play_count: u32,
+    artist_counts: HashMap<String, u32>,
+}
+
+impl ListeningStats {
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS plays (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                track TEXT NOT NULL,
+                artist TEXT NOT NULL,
+                album TEXT NOT NULL,
+                started_at INTEGER NOT NULL,
+                play_duration_secs REAL NOT NULL
+            )",
+        )?;
+
+        Ok(Self {
+            conn,
+            pending: None,
+            session_play_count: 0,
+            artist_counts: HashMap::new(),
+        })
+    }
+
+    /// Call whenever the polling loop detects a genuine track change.
+    /// Persists a record for whatever was playing before and starts timing
+    /// the new track.
+    pub fn on_track_change(&mut self, np: &NowPlaying) {
+        self.finalize_pending();
+        self.pending = Some(PendingPlay {
+            track: np.track.clone(),
+            artist: np.artist.clone(),
+            album: np.album.clone(),
+            started_unix: unix_now_secs(),
+            started_at: Instant::now(),
+        });
+    }
+
+    fn finalize_pending(&mut self) {
+        let Some(play) = self.pending.take() else {
+            return;
+        };
+        let play_duration_secs = play.started_at.elapsed().as_secs_f32();
+
+        let result = self.conn.execute(
+            "INSERT INTO plays (track, artist, album, started_at, play_duration_secs)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                play.track,
+                play.artist,
+                play.album,
+                play.started_unix,
+                play_duration_secs
+            ],
+        );
+        if let Err(err) = result {
+            eprintln!("⚠️  failed to record play: {}", err);
+        }
+
+        self.session_play_count += 1;
+        *self.artist_counts.entry(play.artist).or_insert(0) += 1;
+    }
+
+    pub fn session_play_count(&self) -> u32 {
+        self.session_play_count
+    }
+
+    pub fn most_played_artist(&self) -> Option<(&str, u32)> {
+        self.artist_counts
+            .iter()
+            .max_by_key(|(_, count)| **count)
+            .map(|(artist, count)| (artist.as_str(), *count))
+    }
+}